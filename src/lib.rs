@@ -1,7 +1,10 @@
 use std::collections::{HashMap, HashSet};
 use std::ops::RangeInclusive;
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+use rocket::serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
 pub struct DataPoint {
     pub x: f64,
     pub y: f64,
@@ -13,34 +16,161 @@ impl DataPoint {
     }
 }
 
+/// Controls how a strategy fills gaps outside the known dataset's own min/max.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde", rename_all = "snake_case")]
+pub enum BoundaryMode {
+    /// Leave leading/trailing gaps unfilled. The current, default behavior.
+    None,
+    /// Repeat the first/last known y outward.
+    Hold,
+    /// Continue the slope of the nearest two known points outward.
+    Linear,
+}
+
+impl Default for BoundaryMode {
+    fn default() -> Self {
+        BoundaryMode::None
+    }
+}
+
+/// How to collapse multiple known points that land in the same grid cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde", rename_all = "snake_case")]
+pub enum Aggregation {
+    /// Keep the y of the first point encountered for a cell.
+    First,
+    /// Keep the y of the last point encountered for a cell.
+    Last,
+    /// Average the y of every point that falls in a cell.
+    Mean,
+}
+
+impl Default for Aggregation {
+    fn default() -> Self {
+        Aggregation::First
+    }
+}
+
+/// The grid a dataset's x axis is snapped onto before imputation. Defaults
+/// to unit spacing, matching integer-x behavior.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Resolution {
+    pub step: f64,
+    #[serde(default)]
+    pub aggregation: Aggregation,
+}
+
+impl Default for Resolution {
+    fn default() -> Self {
+        Resolution {
+            step: 1.0,
+            aggregation: Aggregation::default(),
+        }
+    }
+}
+
+impl Resolution {
+    /// The index of the grid cell `x` falls into.
+    fn cell(&self, x: f64) -> i64 {
+        (x / self.step).round() as i64
+    }
+
+    /// The x coordinate a grid cell represents.
+    fn value(&self, cell: i64) -> f64 {
+        cell as f64 * self.step
+    }
+}
+
+/// Hard cap on [`ImbueContext::total_count`]. Without it, a wide axis range
+/// paired with a tiny `resolution.step` forces an arbitrarily large
+/// allocation from a single request.
+pub const MAX_GRID_CELLS: usize = 1_000_000;
+
+#[derive(Debug)]
+pub enum ImbueError {
+    TooManyGridCells,
+}
+
 pub struct ImbueContext {
     pub dataset: Vec<DataPoint>,
     pub total_count: usize,
     pub imbue_count: usize,
     pub axis_min: f64,
     pub axis_max: f64,
+    pub boundary: BoundaryMode,
+    pub resolution: Resolution,
 }
 
 impl ImbueContext {
-    pub fn new(dataset: Vec<DataPoint>) -> Self {
-        let (axis_min, axis_max) = ImbueContext::axis_min_and_max(&dataset);
-        let total_count = ((axis_max - axis_min).abs() + 1.0) as usize;
-        let imbue_count = total_count - dataset.len();
-        ImbueContext {
+    pub fn new(dataset: Vec<DataPoint>) -> Result<Self, ImbueError> {
+        ImbueContext::with_bounds(
+            dataset,
+            BoundaryMode::default(),
+            None,
+            None,
+            Resolution::default(),
+        )
+    }
+
+    /// Like [`ImbueContext::new`], but `axis_min`/`axis_max` can widen the
+    /// range past the dataset so `boundary` can fill the extra room, and
+    /// `resolution` snaps x onto a grid before anything else runs.
+    ///
+    /// Errors if the resulting grid would exceed [`MAX_GRID_CELLS`].
+    pub fn with_bounds(
+        dataset: Vec<DataPoint>,
+        boundary: BoundaryMode,
+        axis_min: Option<f64>,
+        axis_max: Option<f64>,
+        resolution: Resolution,
+    ) -> Result<Self, ImbueError> {
+        let dataset = snap_to_grid(dataset, resolution);
+        if dataset.is_empty() {
+            return Ok(ImbueContext {
+                axis_min: axis_min.unwrap_or(0.0),
+                axis_max: axis_max.unwrap_or(0.0),
+                total_count: 0,
+                imbue_count: 0,
+                dataset,
+                boundary,
+                resolution,
+            });
+        }
+
+        let (dataset_min, dataset_max) = ImbueContext::axis_min_and_max(&dataset);
+        let axis_min = axis_min.map_or(dataset_min, |min| min.min(dataset_min));
+        let axis_max = axis_max.map_or(dataset_max, |max| max.max(dataset_max));
+        let axis_min = resolution.value(resolution.cell(axis_min));
+        let axis_max = resolution.value(resolution.cell(axis_max));
+        let total_count = (((axis_max - axis_min) / resolution.step).abs().round() + 1.0) as usize;
+        if total_count > MAX_GRID_CELLS {
+            return Err(ImbueError::TooManyGridCells);
+        }
+        let imbue_count = total_count.saturating_sub(dataset.len());
+        Ok(ImbueContext {
             dataset,
             axis_min,
             axis_max,
             total_count,
             imbue_count,
-        }
+            boundary,
+            resolution,
+        })
     }
 
+    /// The dataset's axis range expressed as [`Resolution`] grid cells.
     pub fn axis_range(&self) -> RangeInclusive<i64> {
-        self.axis_min as i64..=self.axis_max as i64
+        self.resolution.cell(self.axis_min)..=self.resolution.cell(self.axis_max)
     }
 
+    /// The grid cells already covered by a known point.
     pub fn known_x(&self) -> HashSet<i64> {
-        self.dataset.iter().map(|data| data.x as i64).collect()
+        self.dataset
+            .iter()
+            .map(|data| self.resolution.cell(data.x))
+            .collect()
     }
 
     fn axis_min_and_max(dataset: &Vec<DataPoint>) -> (f64, f64) {
@@ -51,6 +181,35 @@ impl ImbueContext {
     }
 }
 
+/// Snaps every point's x onto `resolution`'s grid, collapsing points that
+/// land in the same cell via its aggregation policy.
+fn snap_to_grid(dataset: Vec<DataPoint>, resolution: Resolution) -> Vec<DataPoint> {
+    let mut cell_order = Vec::new();
+    let mut cell_ys: HashMap<i64, Vec<f64>> = HashMap::new();
+
+    for point in dataset {
+        let cell = resolution.cell(point.x);
+        let ys = cell_ys.entry(cell).or_insert_with(|| {
+            cell_order.push(cell);
+            Vec::new()
+        });
+        ys.push(point.y);
+    }
+
+    cell_order
+        .into_iter()
+        .map(|cell| {
+            let ys = &cell_ys[&cell];
+            let y = match resolution.aggregation {
+                Aggregation::First => ys[0],
+                Aggregation::Last => *ys.last().unwrap(),
+                Aggregation::Mean => ys.iter().sum::<f64>() / ys.len() as f64,
+            };
+            DataPoint::new(resolution.value(cell), y)
+        })
+        .collect()
+}
+
 fn min_and_max(mut accumulator: (f64, f64), item: f64) -> (f64, f64) {
     if item < accumulator.0 {
         accumulator.0 = item;
@@ -68,20 +227,24 @@ pub fn average(context: &ImbueContext) -> Vec<DataPoint> {
         return vec![];
     }
 
+    let resolution = context.resolution;
     let mut sorted_dataset = context.dataset.clone();
-    sorted_dataset.sort_by_key(|datapoint| datapoint.x as i64);
+    sorted_dataset.sort_by(|a, b| a.x.partial_cmp(&b.x).unwrap());
 
-    sorted_dataset
+    let mut imbued: Vec<DataPoint> = sorted_dataset
         .windows(2)
-        .filter(|window| window[0].x as i64 + 1 != window[1].x as i64)
+        .filter(|window| resolution.cell(window[0].x) + 1 != resolution.cell(window[1].x))
         .map(|window| (window[0], window[1]))
-        .flat_map(average_imbue_window)
-        .collect()
+        .flat_map(|window| average_imbue_window(window, resolution))
+        .collect();
+
+    imbued.extend(boundary_imbue(context, &sorted_dataset));
+    imbued
 }
 
-fn average_imbue_window(window: (DataPoint, DataPoint)) -> Vec<DataPoint> {
-    let start = window.0.x as i64 + 1;
-    let end = window.1.x as i64 - 1;
+fn average_imbue_window(window: (DataPoint, DataPoint), resolution: Resolution) -> Vec<DataPoint> {
+    let start = resolution.cell(window.0.x) + 1;
+    let end = resolution.cell(window.1.x) - 1;
     let missing_count = end - start + 1;
 
     // Add one to the missing count so that the last value calculated
@@ -95,8 +258,8 @@ fn average_imbue_window(window: (DataPoint, DataPoint)) -> Vec<DataPoint> {
 
     let mut missing = Vec::with_capacity(missing_count as usize);
     let mut total_change = window.0.y + delta;
-    for x in start..=end {
-        missing.push(DataPoint::new(x as f64, total_change));
+    for cell in start..=end {
+        missing.push(DataPoint::new(resolution.value(cell), total_change));
         total_change += delta;
     }
     missing
@@ -108,12 +271,21 @@ pub fn zeroed(context: &ImbueContext) -> Vec<DataPoint> {
         return vec![];
     }
 
+    let resolution = context.resolution;
+    let mut sorted_dataset = context.dataset.clone();
+    sorted_dataset.sort_by(|a, b| a.x.partial_cmp(&b.x).unwrap());
+
     let known_x = context.known_x();
-    return context
-        .axis_range()
-        .filter(|x| !known_x.contains(&x))
-        .map(|x| DataPoint::new(x as f64, 0.0))
+    let first_known = resolution.cell(sorted_dataset[0].x);
+    let last_known_x = resolution.cell(sorted_dataset.last().unwrap().x);
+
+    let mut imbued_dataset: Vec<DataPoint> = (first_known..=last_known_x)
+        .filter(|cell| !known_x.contains(cell))
+        .map(|cell| DataPoint::new(resolution.value(cell), 0.0))
         .collect();
+
+    imbued_dataset.extend(boundary_imbue(context, &sorted_dataset));
+    imbued_dataset
 }
 
 pub fn last_known(context: &ImbueContext) -> Vec<DataPoint> {
@@ -122,32 +294,319 @@ pub fn last_known(context: &ImbueContext) -> Vec<DataPoint> {
         return vec![];
     }
 
-    let dataset_map = dataset_map(&context.dataset);
+    let resolution = context.resolution;
+    let mut sorted_dataset = context.dataset.clone();
+    sorted_dataset.sort_by(|a, b| a.x.partial_cmp(&b.x).unwrap());
+
+    let dataset_map = dataset_map(&context.dataset, resolution);
+    let first_known = resolution.cell(sorted_dataset[0].x);
+    let last_known_x = resolution.cell(sorted_dataset.last().unwrap().x);
+
     let mut imbued_dataset = Vec::with_capacity(imbue_count);
-    let mut last_known = 0.0;
-    for x in context.axis_range() {
-        if dataset_map.contains_key(&x) {
-            last_known = dataset_map.get(&x).unwrap().clone();
+    let mut last_known = sorted_dataset[0].y;
+    for cell in first_known..=last_known_x {
+        if let Some(y) = dataset_map.get(&cell) {
+            last_known = *y;
         } else {
-            imbued_dataset.push(DataPoint::new(x as f64, last_known))
+            imbued_dataset.push(DataPoint::new(resolution.value(cell), last_known))
         }
     }
 
+    imbued_dataset.extend(boundary_imbue(context, &sorted_dataset));
     imbued_dataset
 }
 
-fn dataset_map(dataset: &Vec<DataPoint>) -> HashMap<i64, f64> {
-    dataset.iter().map(|data| (data.x as i64, data.y)).collect()
+fn dataset_map(dataset: &Vec<DataPoint>, resolution: Resolution) -> HashMap<i64, f64> {
+    dataset
+        .iter()
+        .map(|data| (resolution.cell(data.x), data.y))
+        .collect()
+}
+
+/// Extends the dataset at the leading/trailing edges per [`BoundaryMode`].
+fn boundary_imbue(context: &ImbueContext, sorted_dataset: &[DataPoint]) -> Vec<DataPoint> {
+    if context.boundary == BoundaryMode::None || sorted_dataset.is_empty() {
+        return vec![];
+    }
+
+    let resolution = context.resolution;
+    let mut points = vec![];
+
+    let leading_start = resolution.cell(context.axis_min);
+    let leading_end = resolution.cell(sorted_dataset[0].x) - 1;
+    if leading_start <= leading_end {
+        points.extend(extrapolate_edge(
+            context.boundary,
+            sorted_dataset,
+            resolution,
+            leading_start..=leading_end,
+            true,
+        ));
+    }
+
+    let trailing_start = resolution.cell(sorted_dataset.last().unwrap().x) + 1;
+    let trailing_end = resolution.cell(context.axis_max);
+    if trailing_start <= trailing_end {
+        points.extend(extrapolate_edge(
+            context.boundary,
+            sorted_dataset,
+            resolution,
+            trailing_start..=trailing_end,
+            false,
+        ));
+    }
+
+    points
+}
+
+/// Extrapolates outward from the nearest known point or two, per `mode`.
+/// `sorted_dataset` must be sorted by `x` and non-empty.
+fn extrapolate_edge(
+    mode: BoundaryMode,
+    sorted_dataset: &[DataPoint],
+    resolution: Resolution,
+    range: RangeInclusive<i64>,
+    leading: bool,
+) -> Vec<DataPoint> {
+    let anchor = if leading {
+        sorted_dataset[0]
+    } else {
+        *sorted_dataset.last().unwrap()
+    };
+
+    let slope = match mode {
+        BoundaryMode::None => return vec![],
+        BoundaryMode::Hold => 0.0,
+        BoundaryMode::Linear => {
+            let neighbor_index = if leading {
+                1
+            } else {
+                sorted_dataset.len().saturating_sub(2)
+            };
+            let neighbor = sorted_dataset
+                .get(neighbor_index)
+                .copied()
+                .unwrap_or(anchor);
+            if (neighbor.x - anchor.x).abs() > f64::EPSILON {
+                (neighbor.y - anchor.y) / (neighbor.x - anchor.x)
+            } else {
+                0.0
+            }
+        }
+    };
+
+    range
+        .map(|cell| {
+            let x = resolution.value(cell);
+            DataPoint::new(x, anchor.y + slope * (x - anchor.x))
+        })
+        .collect()
+}
+
+/// Fits a natural cubic spline across known points, falling back to
+/// `average`'s piecewise-linear behavior with fewer than three.
+pub fn spline(context: &ImbueContext) -> Vec<DataPoint> {
+    let imbue_count = context.imbue_count;
+    if imbue_count == 0 {
+        return vec![];
+    }
+
+    let resolution = context.resolution;
+    let mut sorted_dataset = context.dataset.clone();
+    sorted_dataset.sort_by(|a, b| a.x.partial_cmp(&b.x).unwrap());
+
+    if sorted_dataset.len() < 3 {
+        return average(context);
+    }
+
+    let second_derivatives = natural_cubic_spline_second_derivatives(&sorted_dataset);
+
+    let mut imbued: Vec<DataPoint> = sorted_dataset
+        .windows(2)
+        .zip(second_derivatives.windows(2))
+        .filter(|(window, _)| resolution.cell(window[0].x) + 1 != resolution.cell(window[1].x))
+        .flat_map(|(window, m)| spline_imbue_window(window[0], window[1], m[0], m[1], resolution))
+        .collect();
+
+    imbued.extend(boundary_imbue(context, &sorted_dataset));
+    imbued
+}
+
+/// Solves for the spline's second derivatives with the natural boundary
+/// condition `M_0 = M_{n-1} = 0`.
+fn natural_cubic_spline_second_derivatives(dataset: &[DataPoint]) -> Vec<f64> {
+    let n = dataset.len();
+    let h: Vec<f64> = dataset.windows(2).map(|w| w[1].x - w[0].x).collect();
+
+    let mut sub = vec![0.0; n];
+    let mut diag = vec![1.0; n];
+    let mut sup = vec![0.0; n];
+    let mut rhs = vec![0.0; n];
+
+    for i in 1..n - 1 {
+        sub[i] = h[i - 1];
+        diag[i] = 2.0 * (h[i - 1] + h[i]);
+        sup[i] = h[i];
+        rhs[i] = 6.0
+            * ((dataset[i + 1].y - dataset[i].y) / h[i]
+                - (dataset[i].y - dataset[i - 1].y) / h[i - 1]);
+    }
+
+    thomas_algorithm(&sub, &diag, &sup, &rhs)
+}
+
+/// Solves a tridiagonal linear system in O(n) using the Thomas algorithm.
+fn thomas_algorithm(sub: &[f64], diag: &[f64], sup: &[f64], rhs: &[f64]) -> Vec<f64> {
+    let n = diag.len();
+    let mut c_prime = vec![0.0; n];
+    let mut d_prime = vec![0.0; n];
+
+    c_prime[0] = sup[0] / diag[0];
+    d_prime[0] = rhs[0] / diag[0];
+
+    for i in 1..n {
+        let denominator = diag[i] - sub[i] * c_prime[i - 1];
+        c_prime[i] = sup[i] / denominator;
+        d_prime[i] = (rhs[i] - sub[i] * d_prime[i - 1]) / denominator;
+    }
+
+    let mut solution = vec![0.0; n];
+    solution[n - 1] = d_prime[n - 1];
+    for i in (0..n - 1).rev() {
+        solution[i] = d_prime[i] - c_prime[i] * solution[i + 1];
+    }
+
+    solution
+}
+
+fn spline_imbue_window(
+    start: DataPoint,
+    end: DataPoint,
+    m_start: f64,
+    m_end: f64,
+    resolution: Resolution,
+) -> Vec<DataPoint> {
+    let h = end.x - start.x;
+    let first_missing = resolution.cell(start.x) + 1;
+    let last_missing = resolution.cell(end.x) - 1;
+
+    (first_missing..=last_missing)
+        .map(|cell| {
+            let x = resolution.value(cell);
+            let left = m_start * (end.x - x).powi(3) / (6.0 * h)
+                + m_end * (x - start.x).powi(3) / (6.0 * h);
+            let right = (start.y / h - m_start * h / 6.0) * (end.x - x)
+                + (end.y / h - m_end * h / 6.0) * (x - start.x);
+            DataPoint::new(x, left + right)
+        })
+        .collect()
+}
+
+/// A single imputation method. Implement this to plug a custom strategy into
+/// a [`StrategyRegistry`] alongside the built-in `average`/`zeroed`/`last_known`
+/// strategies. `Send + Sync` so a registry can be shared across threads.
+pub trait Imbuer: Send + Sync {
+    /// The name requests use to select this strategy, e.g. `"average"`.
+    fn name(&self) -> &str;
+
+    fn imbue(&self, context: &ImbueContext) -> Vec<DataPoint>;
+}
+
+struct AverageImbuer;
+
+impl Imbuer for AverageImbuer {
+    fn name(&self) -> &str {
+        "average"
+    }
+
+    fn imbue(&self, context: &ImbueContext) -> Vec<DataPoint> {
+        average(context)
+    }
+}
+
+struct ZeroedImbuer;
+
+impl Imbuer for ZeroedImbuer {
+    fn name(&self) -> &str {
+        "zeroed"
+    }
+
+    fn imbue(&self, context: &ImbueContext) -> Vec<DataPoint> {
+        zeroed(context)
+    }
+}
+
+struct LastKnownImbuer;
+
+impl Imbuer for LastKnownImbuer {
+    fn name(&self) -> &str {
+        "last_known"
+    }
+
+    fn imbue(&self, context: &ImbueContext) -> Vec<DataPoint> {
+        last_known(context)
+    }
+}
+
+struct SplineImbuer;
+
+impl Imbuer for SplineImbuer {
+    fn name(&self) -> &str {
+        "spline"
+    }
+
+    fn imbue(&self, context: &ImbueContext) -> Vec<DataPoint> {
+        spline(context)
+    }
+}
+
+/// A lookup of [`Imbuer`]s by name. The `/imbue` endpoint resolves a request's
+/// `strategy` field against a registry instead of matching a closed enum, so
+/// downstream users can register their own strategies and expose them over
+/// the same endpoint.
+#[derive(Default)]
+pub struct StrategyRegistry {
+    strategies: HashMap<String, Box<dyn Imbuer>>,
+}
+
+impl StrategyRegistry {
+    pub fn new() -> Self {
+        StrategyRegistry {
+            strategies: HashMap::new(),
+        }
+    }
+
+    /// Builds a registry seeded with the crate's built-in strategies:
+    /// `average`, `zeroed`, and `last_known`.
+    pub fn with_builtins() -> Self {
+        let mut registry = StrategyRegistry::new();
+        registry.register(Box::new(AverageImbuer));
+        registry.register(Box::new(ZeroedImbuer));
+        registry.register(Box::new(LastKnownImbuer));
+        registry.register(Box::new(SplineImbuer));
+        registry
+    }
+
+    pub fn register(&mut self, imbuer: Box<dyn Imbuer>) {
+        self.strategies.insert(imbuer.name().to_string(), imbuer);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn Imbuer> {
+        self.strategies.get(name).map(Box::as_ref)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{DataPoint, ImbueContext};
+    use crate::{
+        Aggregation, BoundaryMode, DataPoint, ImbueContext, ImbueError, Resolution,
+        StrategyRegistry,
+    };
 
     #[test]
     fn test_average_imbue() {
         let dataset = vec![DataPoint::new(1.0, 123.0), DataPoint::new(5.0, 43.0)];
-        let context = ImbueContext::new(dataset);
+        let context = ImbueContext::new(dataset).unwrap();
         let imbued_dataset = crate::average(&context);
 
         let expected_dataset: Vec<DataPoint> = vec![
@@ -165,7 +624,7 @@ mod tests {
             DataPoint::new(5.0, 43.0),
             DataPoint::new(8.0, 80.0),
         ];
-        let context = ImbueContext::new(dataset);
+        let context = ImbueContext::new(dataset).unwrap();
         let imbued_dataset = crate::average(&context);
 
         let expected_dataset: Vec<DataPoint> = vec![
@@ -181,7 +640,7 @@ mod tests {
     #[test]
     fn test_average_imbue_with_flat_average() {
         let dataset = vec![DataPoint::new(1.0, 123.0), DataPoint::new(5.0, 123.0)];
-        let context = ImbueContext::new(dataset);
+        let context = ImbueContext::new(dataset).unwrap();
         let imbued_dataset = crate::average(&context);
 
         let expected_dataset: Vec<DataPoint> = vec![
@@ -195,7 +654,7 @@ mod tests {
     #[test]
     fn test_zeroed_imbue() {
         let dataset = vec![DataPoint::new(1.0, 123.0), DataPoint::new(5.0, 43.0)];
-        let context = ImbueContext::new(dataset);
+        let context = ImbueContext::new(dataset).unwrap();
         let imbued_dataset = crate::zeroed(&context);
 
         let expected_dataset = vec![
@@ -213,7 +672,7 @@ mod tests {
             DataPoint::new(1.0, 123.0),
             DataPoint::new(5.0, 43.0),
         ];
-        let context = ImbueContext::new(dataset);
+        let context = ImbueContext::new(dataset).unwrap();
         let imbued_dataset = crate::zeroed(&context);
 
         let expected_dataset = vec![
@@ -230,6 +689,29 @@ mod tests {
         assert_eq!(imbued_dataset, expected_dataset);
     }
 
+    #[test]
+    fn test_zeroed_imbue_with_hold_boundary() {
+        let dataset = vec![DataPoint::new(1.0, 123.0), DataPoint::new(4.0, 56.0)];
+        let context = ImbueContext::with_bounds(
+            dataset,
+            BoundaryMode::Hold,
+            Some(-1.0),
+            Some(6.0),
+            Resolution::default(),
+        ).unwrap();
+        let imbued_dataset = crate::zeroed(&context);
+
+        let expected_dataset: Vec<DataPoint> = vec![
+            DataPoint::new(2.0, 0.0),
+            DataPoint::new(3.0, 0.0),
+            DataPoint::new(-1.0, 123.0),
+            DataPoint::new(0.0, 123.0),
+            DataPoint::new(5.0, 56.0),
+            DataPoint::new(6.0, 56.0),
+        ];
+        assert_eq!(imbued_dataset, expected_dataset);
+    }
+
     #[test]
     fn test_last_known_imbue() {
         let dataset = vec![
@@ -237,7 +719,7 @@ mod tests {
             DataPoint::new(1.0, 123.0),
             DataPoint::new(4.0, 56.0),
         ];
-        let context = ImbueContext::new(dataset);
+        let context = ImbueContext::new(dataset).unwrap();
         let imbued_dataset = crate::last_known(&context);
 
         let expected_dataset: Vec<DataPoint> = vec![
@@ -257,7 +739,7 @@ mod tests {
             DataPoint::new(4.0, 56.0),
             DataPoint::new(7.0, 84.0),
         ];
-        let context = ImbueContext::new(dataset);
+        let context = ImbueContext::new(dataset).unwrap();
         let imbued_dataset = crate::last_known(&context);
 
         let expected_dataset: Vec<DataPoint> = vec![
@@ -270,4 +752,227 @@ mod tests {
         ];
         assert_eq!(imbued_dataset, expected_dataset);
     }
+
+    #[test]
+    fn test_last_known_imbue_with_hold_boundary() {
+        let dataset = vec![DataPoint::new(1.0, 123.0), DataPoint::new(4.0, 56.0)];
+        let context = ImbueContext::with_bounds(
+            dataset,
+            BoundaryMode::Hold,
+            Some(-1.0),
+            Some(6.0),
+            Resolution::default(),
+        ).unwrap();
+        let imbued_dataset = crate::last_known(&context);
+
+        let expected_dataset: Vec<DataPoint> = vec![
+            DataPoint::new(2.0, 123.0),
+            DataPoint::new(3.0, 123.0),
+            DataPoint::new(-1.0, 123.0),
+            DataPoint::new(0.0, 123.0),
+            DataPoint::new(5.0, 56.0),
+            DataPoint::new(6.0, 56.0),
+        ];
+        assert_eq!(imbued_dataset, expected_dataset);
+    }
+
+    #[test]
+    fn test_average_imbue_with_hold_boundary() {
+        let dataset = vec![DataPoint::new(1.0, 10.0), DataPoint::new(5.0, 50.0)];
+        let context = ImbueContext::with_bounds(
+            dataset,
+            BoundaryMode::Hold,
+            Some(-2.0),
+            Some(8.0),
+            Resolution::default(),
+        ).unwrap();
+        let imbued_dataset = crate::average(&context);
+
+        let expected_dataset = vec![
+            DataPoint::new(2.0, 20.0),
+            DataPoint::new(3.0, 30.0),
+            DataPoint::new(4.0, 40.0),
+            DataPoint::new(-2.0, 10.0),
+            DataPoint::new(-1.0, 10.0),
+            DataPoint::new(0.0, 10.0),
+            DataPoint::new(6.0, 50.0),
+            DataPoint::new(7.0, 50.0),
+            DataPoint::new(8.0, 50.0),
+        ];
+        assert_eq!(imbued_dataset, expected_dataset);
+    }
+
+    #[test]
+    fn test_average_imbue_with_linear_boundary() {
+        let dataset = vec![DataPoint::new(1.0, 10.0), DataPoint::new(3.0, 30.0)];
+        let context = ImbueContext::with_bounds(
+            dataset,
+            BoundaryMode::Linear,
+            Some(-1.0),
+            Some(5.0),
+            Resolution::default(),
+        ).unwrap();
+        let imbued_dataset = crate::average(&context);
+
+        let expected_dataset = vec![
+            DataPoint::new(2.0, 20.0),
+            DataPoint::new(-1.0, -10.0),
+            DataPoint::new(0.0, 0.0),
+            DataPoint::new(4.0, 40.0),
+            DataPoint::new(5.0, 50.0),
+        ];
+        assert_eq!(imbued_dataset, expected_dataset);
+    }
+
+    #[test]
+    fn test_average_imbue_with_default_boundary_is_unaffected_by_wider_range() {
+        let dataset = vec![DataPoint::new(1.0, 123.0), DataPoint::new(5.0, 43.0)];
+        let context = ImbueContext::with_bounds(
+            dataset,
+            BoundaryMode::None,
+            Some(-2.0),
+            Some(8.0),
+            Resolution::default(),
+        ).unwrap();
+        let imbued_dataset = crate::average(&context);
+
+        let expected_dataset: Vec<DataPoint> = vec![
+            DataPoint::new(2.0, 103.0),
+            DataPoint::new(3.0, 83.0),
+            DataPoint::new(4.0, 63.0),
+        ];
+        assert_eq!(imbued_dataset, expected_dataset);
+    }
+
+    #[test]
+    fn test_spline_imbue() {
+        let dataset = vec![
+            DataPoint::new(0.0, 0.0),
+            DataPoint::new(2.0, 4.0),
+            DataPoint::new(4.0, 0.0),
+        ];
+        let context = ImbueContext::new(dataset).unwrap();
+        let imbued_dataset = crate::spline(&context);
+
+        let expected_dataset: Vec<DataPoint> =
+            vec![DataPoint::new(1.0, 2.75), DataPoint::new(3.0, 2.75)];
+        assert_eq!(imbued_dataset, expected_dataset);
+    }
+
+    #[test]
+    fn test_spline_imbue_with_fewer_than_three_points_falls_back_to_linear() {
+        let dataset = vec![DataPoint::new(1.0, 123.0), DataPoint::new(5.0, 43.0)];
+        let context = ImbueContext::new(dataset).unwrap();
+        let imbued_dataset = crate::spline(&context);
+
+        let expected_dataset: Vec<DataPoint> = vec![
+            DataPoint::new(2.0, 103.0),
+            DataPoint::new(3.0, 83.0),
+            DataPoint::new(4.0, 63.0),
+        ];
+        assert_eq!(imbued_dataset, expected_dataset);
+    }
+
+    #[test]
+    fn test_spline_imbue_with_no_missing_points() {
+        let dataset = vec![
+            DataPoint::new(1.0, 1.0),
+            DataPoint::new(2.0, 2.0),
+            DataPoint::new(3.0, 3.0),
+        ];
+        let context = ImbueContext::new(dataset).unwrap();
+        let imbued_dataset = crate::spline(&context);
+
+        assert_eq!(imbued_dataset, vec![]);
+    }
+
+    #[test]
+    fn test_average_imbue_with_fractional_resolution() {
+        let dataset = vec![DataPoint::new(1.0, 10.0), DataPoint::new(3.0, 50.0)];
+        let resolution = Resolution {
+            step: 0.5,
+            aggregation: Aggregation::default(),
+        };
+        let context =
+            ImbueContext::with_bounds(dataset, BoundaryMode::default(), None, None, resolution).unwrap();
+        let imbued_dataset = crate::average(&context);
+
+        let expected_dataset = vec![
+            DataPoint::new(1.5, 20.0),
+            DataPoint::new(2.0, 30.0),
+            DataPoint::new(2.5, 40.0),
+        ];
+        assert_eq!(imbued_dataset, expected_dataset);
+    }
+
+    #[test]
+    fn test_resolution_collapses_duplicate_cells_by_first() {
+        let dataset = vec![DataPoint::new(0.6, 10.0), DataPoint::new(1.4, 30.0)];
+        let context = ImbueContext::new(dataset).unwrap();
+
+        assert_eq!(context.dataset, vec![DataPoint::new(1.0, 10.0)]);
+    }
+
+    #[test]
+    fn test_resolution_collapses_duplicate_cells_by_last() {
+        let dataset = vec![DataPoint::new(0.6, 10.0), DataPoint::new(1.4, 30.0)];
+        let resolution = Resolution {
+            step: 1.0,
+            aggregation: Aggregation::Last,
+        };
+        let context =
+            ImbueContext::with_bounds(dataset, BoundaryMode::default(), None, None, resolution).unwrap();
+
+        assert_eq!(context.dataset, vec![DataPoint::new(1.0, 30.0)]);
+    }
+
+    #[test]
+    fn test_resolution_collapses_duplicate_cells_by_mean() {
+        let dataset = vec![DataPoint::new(0.6, 10.0), DataPoint::new(1.4, 30.0)];
+        let resolution = Resolution {
+            step: 1.0,
+            aggregation: Aggregation::Mean,
+        };
+        let context =
+            ImbueContext::with_bounds(dataset, BoundaryMode::default(), None, None, resolution).unwrap();
+
+        assert_eq!(context.dataset, vec![DataPoint::new(1.0, 20.0)]);
+    }
+
+    #[test]
+    fn test_empty_dataset_does_not_panic() {
+        let context = ImbueContext::new(vec![]).unwrap();
+
+        assert_eq!(context.imbue_count, 0);
+    }
+
+    #[test]
+    fn test_with_bounds_rejects_grid_over_the_cell_cap() {
+        let dataset = vec![DataPoint::new(0.0, 0.0), DataPoint::new(1_000_000.0, 0.0)];
+        let resolution = Resolution {
+            step: 0.001,
+            aggregation: Aggregation::default(),
+        };
+        let result =
+            ImbueContext::with_bounds(dataset, BoundaryMode::default(), None, None, resolution);
+
+        assert!(matches!(result, Err(ImbueError::TooManyGridCells)));
+    }
+
+    #[test]
+    fn test_registry_resolves_builtins_by_name() {
+        let registry = StrategyRegistry::with_builtins();
+
+        assert_eq!(registry.get("average").unwrap().name(), "average");
+        assert_eq!(registry.get("zeroed").unwrap().name(), "zeroed");
+        assert_eq!(registry.get("last_known").unwrap().name(), "last_known");
+        assert_eq!(registry.get("spline").unwrap().name(), "spline");
+    }
+
+    #[test]
+    fn test_registry_unknown_strategy_is_none() {
+        let registry = StrategyRegistry::with_builtins();
+
+        assert!(registry.get("nonexistent").is_none());
+    }
 }
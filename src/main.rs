@@ -1,29 +1,58 @@
 #[macro_use]
 extern crate rocket;
 
+use std::io::Cursor;
+
+use rayon::prelude::*;
+use rocket::data::{self, Data, FromData, ToByteUnit};
+use rocket::http::{ContentType, Status};
+use rocket::response::{self, Responder, Response};
 use rocket::serde::json::Json;
 use rocket::serde::{Deserialize, Serialize};
+use rocket::{Request, State};
 
-use crate::imbue::{average_imbue, last_known_imbue, zeroed_imbue, DataPoint, ImbueContext};
-
-mod imbue;
+use imbue::{
+    Aggregation, BoundaryMode, DataPoint, ImbueContext, ImbueError, Imbuer, Resolution,
+    StrategyRegistry,
+};
 
 #[derive(Serialize, Deserialize)]
 #[serde(crate = "rocket::serde")]
 struct ImbueRequest {
     dataset: Vec<DataPoint>,
-    strategy: ImbueStrategy,
+    strategy: String,
+    #[serde(default)]
+    boundary: BoundaryMode,
+    axis_min: Option<f64>,
+    axis_max: Option<f64>,
+    #[serde(default)]
+    resolution: Resolution,
 }
 
 impl ImbueRequest {
-    fn new(dataset: Vec<DataPoint>, strategy: ImbueStrategy) -> Self {
-        ImbueRequest { dataset, strategy }
+    fn new(dataset: Vec<DataPoint>, strategy: impl Into<String>) -> Self {
+        ImbueRequest {
+            dataset,
+            strategy: strategy.into(),
+            boundary: BoundaryMode::default(),
+            axis_min: None,
+            axis_max: None,
+            resolution: Resolution::default(),
+        }
     }
 }
 
-impl From<ImbueRequest> for ImbueContext {
-    fn from(request: ImbueRequest) -> Self {
-        ImbueContext::new(request.dataset)
+impl TryFrom<ImbueRequest> for ImbueContext {
+    type Error = ImbueError;
+
+    fn try_from(request: ImbueRequest) -> Result<Self, Self::Error> {
+        ImbueContext::with_bounds(
+            request.dataset,
+            request.boundary,
+            request.axis_min,
+            request.axis_max,
+            request.resolution,
+        )
     }
 }
 
@@ -39,40 +68,171 @@ impl ImbueResponse {
     }
 }
 
-#[derive(Serialize, Deserialize)]
-#[serde(crate = "rocket::serde", rename_all = "snake_case")]
-enum ImbueStrategy {
-    Average,
-    Zeroed,
-    LastKnown,
+#[post("/imbue", data = "<request>", format = "json")]
+fn imbue_data(
+    request: Json<ImbueRequest>,
+    registry: &State<StrategyRegistry>,
+) -> Result<Json<ImbueResponse>, Status> {
+    let strategy = registry.get(&request.strategy).ok_or(Status::BadRequest)?;
+    if request.resolution.step <= 0.0 {
+        return Err(Status::BadRequest);
+    }
+    let context = ImbueContext::try_from(request.0).map_err(|_| Status::BadRequest)?;
+    let imbued_dataset = strategy.imbue(&context);
+
+    Ok(Json(ImbueResponse::new(imbued_dataset)))
 }
 
-#[post("/imbue", data = "<request>", format = "json")]
-fn imbue_data(request: Json<ImbueRequest>) -> Json<ImbueResponse> {
-    let imbue = match request.strategy {
-        ImbueStrategy::Average => average_imbue,
-        ImbueStrategy::Zeroed => zeroed_imbue,
-        ImbueStrategy::LastKnown => last_known_imbue,
+/// Imputes many independent datasets in one round-trip, in parallel.
+///
+/// Runs on [`rocket::tokio::task::block_in_place`] so rayon's parallel work
+/// doesn't monopolize the async executor's worker thread for the whole batch.
+#[post("/imbue/batch", data = "<requests>", format = "json")]
+async fn imbue_batch(
+    requests: Json<Vec<ImbueRequest>>,
+    registry: &State<StrategyRegistry>,
+) -> Result<Json<Vec<ImbueResponse>>, Status> {
+    rocket::tokio::task::block_in_place(|| {
+        requests
+            .0
+            .into_par_iter()
+            .map(|request| {
+                let strategy = registry.get(&request.strategy).ok_or(Status::BadRequest)?;
+                if request.resolution.step <= 0.0 {
+                    return Err(Status::BadRequest);
+                }
+                let context = ImbueContext::try_from(request).map_err(|_| Status::BadRequest)?;
+                Ok(ImbueResponse::new(strategy.imbue(&context)))
+            })
+            .collect::<Result<Vec<_>, Status>>()
+    })
+    .map(Json)
+}
+
+/// A two-column `x,y` dataset parsed from a `text/csv` request body.
+struct CsvDataset(Vec<DataPoint>);
+
+#[derive(Debug)]
+enum CsvError {
+    Io(std::io::Error),
+    TooLarge,
+    Malformed(String),
+}
+
+#[rocket::async_trait]
+impl<'r> FromData<'r> for CsvDataset {
+    type Error = CsvError;
+
+    async fn from_data(_request: &'r Request<'_>, data: Data<'r>) -> data::Outcome<'r, Self> {
+        let body = match data.open(1.mebibytes()).into_string().await {
+            Ok(body) if body.is_complete() => body.into_inner(),
+            Ok(_) => return data::Outcome::Error((Status::PayloadTooLarge, CsvError::TooLarge)),
+            Err(e) => return data::Outcome::Error((Status::InternalServerError, CsvError::Io(e))),
+        };
+
+        match parse_csv(&body) {
+            Ok(dataset) => data::Outcome::Success(CsvDataset(dataset)),
+            Err(e) => data::Outcome::Error((Status::BadRequest, e)),
+        }
+    }
+}
+
+fn parse_csv(body: &str) -> Result<Vec<DataPoint>, CsvError> {
+    body.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let mut columns = line.splitn(2, ',');
+            let malformed = || CsvError::Malformed(line.to_string());
+            let x = columns.next().ok_or_else(malformed)?.trim();
+            let y = columns.next().ok_or_else(malformed)?.trim();
+            let x: f64 = x.parse().map_err(|_| malformed())?;
+            let y: f64 = y.parse().map_err(|_| malformed())?;
+            if !x.is_finite() || !y.is_finite() {
+                return Err(malformed());
+            }
+            Ok(DataPoint::new(x, y))
+        })
+        .collect()
+}
+
+fn to_csv(dataset: &[DataPoint]) -> String {
+    dataset
+        .iter()
+        .map(|point| format!("{},{}\n", point.x, point.y))
+        .collect()
+}
+
+/// A `text/csv` response body, one `x,y` point per line.
+struct CsvResponse(String);
+
+impl<'r> Responder<'r, 'static> for CsvResponse {
+    fn respond_to(self, _: &'r Request<'_>) -> response::Result<'static> {
+        Response::build()
+            .header(ContentType::CSV)
+            .sized_body(self.0.len(), Cursor::new(self.0))
+            .ok()
+    }
+}
+
+#[post(
+    "/imbue?<strategy>&<boundary>&<axis_min>&<axis_max>&<resolution_step>&<resolution_aggregation>",
+    data = "<body>",
+    format = "text/csv",
+    rank = 2
+)]
+fn imbue_data_csv(
+    strategy: String,
+    boundary: Option<String>,
+    axis_min: Option<f64>,
+    axis_max: Option<f64>,
+    resolution_step: Option<f64>,
+    resolution_aggregation: Option<String>,
+    body: CsvDataset,
+    registry: &State<StrategyRegistry>,
+) -> Result<CsvResponse, Status> {
+    let strategy = registry.get(&strategy).ok_or(Status::BadRequest)?;
+    let boundary = match boundary.as_deref() {
+        None => BoundaryMode::default(),
+        Some("none") => BoundaryMode::None,
+        Some("hold") => BoundaryMode::Hold,
+        Some("linear") => BoundaryMode::Linear,
+        Some(_) => return Err(Status::BadRequest),
+    };
+    let aggregation = match resolution_aggregation.as_deref() {
+        None => Aggregation::default(),
+        Some("first") => Aggregation::First,
+        Some("last") => Aggregation::Last,
+        Some("mean") => Aggregation::Mean,
+        Some(_) => return Err(Status::BadRequest),
+    };
+    let resolution = Resolution {
+        step: resolution_step.unwrap_or(1.0),
+        aggregation,
     };
-    let context = &ImbueContext::from(request.0);
-    let imbued_dataset = imbue(context);
+    if resolution.step <= 0.0 {
+        return Err(Status::BadRequest);
+    }
+    let context = ImbueContext::with_bounds(body.0, boundary, axis_min, axis_max, resolution)
+        .map_err(|_| Status::BadRequest)?;
+    let imbued_dataset = strategy.imbue(&context);
 
-    Json(ImbueResponse::new(imbued_dataset))
+    Ok(CsvResponse(to_csv(&imbued_dataset)))
 }
 
 // Will need this later https://cprimozic.net/blog/rust-rocket-cloud-run/#deploying
 #[launch]
 fn rocket() -> _ {
-    rocket::build().mount("/", routes![imbue_data])
+    rocket::build()
+        .manage(StrategyRegistry::with_builtins())
+        .mount("/", routes![imbue_data, imbue_data_csv, imbue_batch])
 }
 
 #[cfg(test)]
 mod server_tests {
-    use rocket::http::Status;
+    use rocket::http::{ContentType, Status};
     use rocket::local::blocking::Client;
-    use rocket::serde::json::Json;
 
-    use crate::{DataPoint, ImbueRequest, ImbueResponse, ImbueStrategy};
+    use crate::{DataPoint, ImbueRequest, ImbueResponse};
 
     use super::rocket;
 
@@ -85,16 +245,13 @@ mod server_tests {
                 DataPoint::new(3.0, 3.0),
                 DataPoint::new(5.0, 5.0),
             ],
-            ImbueStrategy::Average,
+            "average",
         );
         let response = client.post("/imbue").json(&body).dispatch();
         assert_eq!(response.status(), Status::Ok);
 
         let result: ImbueResponse = response.into_json().unwrap();
-        let expected_result = vec![
-            DataPoint::new(2.0, 2.0),
-            DataPoint::new(4.0, 4.0)
-        ];
+        let expected_result = vec![DataPoint::new(2.0, 2.0), DataPoint::new(4.0, 4.0)];
         assert_eq!(result.dataset, expected_result)
     }
 
@@ -107,16 +264,13 @@ mod server_tests {
                 DataPoint::new(3.0, 3.0),
                 DataPoint::new(5.0, 5.0),
             ],
-            ImbueStrategy::Zeroed,
+            "zeroed",
         );
         let response = client.post("/imbue").json(&body).dispatch();
         assert_eq!(response.status(), Status::Ok);
 
         let result: ImbueResponse = response.into_json().unwrap();
-        let expected_result = vec![
-            DataPoint::new(2.0, 0.0),
-            DataPoint::new(4.0, 0.0)
-        ];
+        let expected_result = vec![DataPoint::new(2.0, 0.0), DataPoint::new(4.0, 0.0)];
         assert_eq!(result.dataset, expected_result)
     }
 
@@ -129,16 +283,251 @@ mod server_tests {
                 DataPoint::new(3.0, 3.0),
                 DataPoint::new(5.0, 5.0),
             ],
-            ImbueStrategy::LastKnown,
+            "last_known",
         );
         let response = client.post("/imbue").json(&body).dispatch();
         assert_eq!(response.status(), Status::Ok);
 
+        let result: ImbueResponse = response.into_json().unwrap();
+        let expected_result = vec![DataPoint::new(2.0, 1.0), DataPoint::new(4.0, 3.0)];
+        assert_eq!(result.dataset, expected_result)
+    }
+
+    #[test]
+    fn test_unknown_strategy_returns_bad_request() {
+        let client = Client::tracked(rocket()).expect("Valid rocket instance required");
+        let body = ImbueRequest::new(
+            vec![DataPoint::new(1.0, 1.0), DataPoint::new(3.0, 3.0)],
+            "nonexistent",
+        );
+        let response = client.post("/imbue").json(&body).dispatch();
+        assert_eq!(response.status(), Status::BadRequest);
+    }
+
+    #[test]
+    fn test_csv_average_imbue() {
+        let client = Client::tracked(rocket()).expect("Valid rocket instance required");
+        let response = client
+            .post("/imbue?strategy=average")
+            .header(ContentType::CSV)
+            .body("1,1\n3,3\n5,5\n")
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.content_type(), Some(ContentType::CSV));
+        assert_eq!(response.into_string().unwrap(), "2,2\n4,4\n");
+    }
+
+    #[test]
+    fn test_csv_unknown_strategy_returns_bad_request() {
+        let client = Client::tracked(rocket()).expect("Valid rocket instance required");
+        let response = client
+            .post("/imbue?strategy=nonexistent")
+            .header(ContentType::CSV)
+            .body("1,1\n3,3\n")
+            .dispatch();
+        assert_eq!(response.status(), Status::BadRequest);
+    }
+
+    #[test]
+    fn test_csv_malformed_body_returns_bad_request() {
+        let client = Client::tracked(rocket()).expect("Valid rocket instance required");
+        let response = client
+            .post("/imbue?strategy=average")
+            .header(ContentType::CSV)
+            .body("not,a,number\n")
+            .dispatch();
+        assert_eq!(response.status(), Status::BadRequest);
+    }
+
+    #[test]
+    fn test_csv_non_finite_body_returns_bad_request() {
+        let client = Client::tracked(rocket()).expect("Valid rocket instance required");
+        let response = client
+            .post("/imbue?strategy=average")
+            .header(ContentType::CSV)
+            .body("1,1\nnan,3\n")
+            .dispatch();
+        assert_eq!(response.status(), Status::BadRequest);
+    }
+
+    #[test]
+    fn test_csv_hold_boundary() {
+        let client = Client::tracked(rocket()).expect("Valid rocket instance required");
+        let response = client
+            .post("/imbue?strategy=average&boundary=hold&axis_min=0&axis_max=6")
+            .header(ContentType::CSV)
+            .body("1,10\n5,50\n")
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(
+            response.into_string().unwrap(),
+            "2,20\n3,30\n4,40\n0,10\n6,50\n"
+        );
+    }
+
+    #[test]
+    fn test_average_imbue_with_hold_boundary() {
+        let client = Client::tracked(rocket()).expect("Valid rocket instance required");
+        let body = ImbueRequest {
+            dataset: vec![DataPoint::new(1.0, 10.0), DataPoint::new(5.0, 50.0)],
+            strategy: "average".to_string(),
+            boundary: imbue::BoundaryMode::Hold,
+            axis_min: Some(0.0),
+            axis_max: Some(6.0),
+            resolution: imbue::Resolution::default(),
+        };
+        let response = client.post("/imbue").json(&body).dispatch();
+        assert_eq!(response.status(), Status::Ok);
+
+        let result: ImbueResponse = response.into_json().unwrap();
+        let expected_result = vec![
+            DataPoint::new(2.0, 20.0),
+            DataPoint::new(3.0, 30.0),
+            DataPoint::new(4.0, 40.0),
+            DataPoint::new(0.0, 10.0),
+            DataPoint::new(6.0, 50.0),
+        ];
+        assert_eq!(result.dataset, expected_result)
+    }
+
+    #[test]
+    fn test_batch_imbue_processes_in_order() {
+        let client = Client::tracked(rocket()).expect("Valid rocket instance required");
+        let body = vec![
+            ImbueRequest::new(
+                vec![
+                    DataPoint::new(1.0, 1.0),
+                    DataPoint::new(3.0, 3.0),
+                    DataPoint::new(5.0, 5.0),
+                ],
+                "average",
+            ),
+            ImbueRequest::new(
+                vec![
+                    DataPoint::new(1.0, 1.0),
+                    DataPoint::new(3.0, 3.0),
+                    DataPoint::new(5.0, 5.0),
+                ],
+                "zeroed",
+            ),
+        ];
+        let response = client.post("/imbue/batch").json(&body).dispatch();
+        assert_eq!(response.status(), Status::Ok);
+
+        let result: Vec<ImbueResponse> = response.into_json().unwrap();
+        assert_eq!(
+            result[0].dataset,
+            vec![DataPoint::new(2.0, 2.0), DataPoint::new(4.0, 4.0)]
+        );
+        assert_eq!(
+            result[1].dataset,
+            vec![DataPoint::new(2.0, 0.0), DataPoint::new(4.0, 0.0)]
+        );
+    }
+
+    #[test]
+    fn test_batch_imbue_unknown_strategy_returns_bad_request() {
+        let client = Client::tracked(rocket()).expect("Valid rocket instance required");
+        let body = vec![ImbueRequest::new(
+            vec![DataPoint::new(1.0, 1.0), DataPoint::new(3.0, 3.0)],
+            "nonexistent",
+        )];
+        let response = client.post("/imbue/batch").json(&body).dispatch();
+        assert_eq!(response.status(), Status::BadRequest);
+    }
+
+    #[test]
+    fn test_average_imbue_with_fractional_resolution() {
+        let client = Client::tracked(rocket()).expect("Valid rocket instance required");
+        let body = ImbueRequest {
+            dataset: vec![DataPoint::new(1.0, 10.0), DataPoint::new(3.0, 50.0)],
+            strategy: "average".to_string(),
+            boundary: imbue::BoundaryMode::default(),
+            axis_min: None,
+            axis_max: None,
+            resolution: imbue::Resolution {
+                step: 0.5,
+                aggregation: imbue::Aggregation::default(),
+            },
+        };
+        let response = client.post("/imbue").json(&body).dispatch();
+        assert_eq!(response.status(), Status::Ok);
+
         let result: ImbueResponse = response.into_json().unwrap();
         let expected_result = vec![
-            DataPoint::new(2.0, 1.0),
-            DataPoint::new(4.0, 3.0)
+            DataPoint::new(1.5, 20.0),
+            DataPoint::new(2.0, 30.0),
+            DataPoint::new(2.5, 40.0),
         ];
         assert_eq!(result.dataset, expected_result)
     }
+
+    #[test]
+    fn test_csv_resolution_collapses_duplicate_cells_by_mean() {
+        let client = Client::tracked(rocket()).expect("Valid rocket instance required");
+        let response = client
+            .post("/imbue?strategy=zeroed&resolution_step=1&resolution_aggregation=mean")
+            .header(ContentType::CSV)
+            .body("0.6,10\n1.4,30\n5,5\n")
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.into_string().unwrap(), "2,0\n3,0\n4,0\n");
+    }
+
+    #[test]
+    fn test_csv_unknown_resolution_aggregation_returns_bad_request() {
+        let client = Client::tracked(rocket()).expect("Valid rocket instance required");
+        let response = client
+            .post("/imbue?strategy=average&resolution_aggregation=nonexistent")
+            .header(ContentType::CSV)
+            .body("1,1\n3,3\n")
+            .dispatch();
+        assert_eq!(response.status(), Status::BadRequest);
+    }
+
+    #[test]
+    fn test_csv_non_positive_resolution_step_returns_bad_request() {
+        let client = Client::tracked(rocket()).expect("Valid rocket instance required");
+        let response = client
+            .post("/imbue?strategy=average&resolution_step=0")
+            .header(ContentType::CSV)
+            .body("1,1\n3,3\n")
+            .dispatch();
+        assert_eq!(response.status(), Status::BadRequest);
+    }
+
+    #[test]
+    fn test_non_positive_resolution_step_returns_bad_request() {
+        let client = Client::tracked(rocket()).expect("Valid rocket instance required");
+        let mut body = ImbueRequest::new(
+            vec![DataPoint::new(1.0, 1.0), DataPoint::new(3.0, 3.0)],
+            "average",
+        );
+        body.resolution.step = 0.0;
+        let response = client.post("/imbue").json(&body).dispatch();
+        assert_eq!(response.status(), Status::BadRequest);
+    }
+
+    #[test]
+    fn test_grid_over_the_cell_cap_returns_bad_request() {
+        let client = Client::tracked(rocket()).expect("Valid rocket instance required");
+        let mut body = ImbueRequest::new(
+            vec![DataPoint::new(0.0, 0.0), DataPoint::new(1_000_000.0, 0.0)],
+            "average",
+        );
+        body.resolution.step = 0.001;
+        let response = client.post("/imbue").json(&body).dispatch();
+        assert_eq!(response.status(), Status::BadRequest);
+    }
+
+    #[test]
+    fn test_csv_grid_over_the_cell_cap_returns_bad_request() {
+        let client = Client::tracked(rocket()).expect("Valid rocket instance required");
+        let response = client
+            .post("/imbue?strategy=average&axis_min=0&axis_max=1000000&resolution_step=0.001")
+            .header(ContentType::CSV)
+            .body("1,1\n3,3\n")
+            .dispatch();
+        assert_eq!(response.status(), Status::BadRequest);
+    }
 }